@@ -0,0 +1,26 @@
+use diesel::sqlite::SqliteConnection;
+use std::sync::Mutex;
+
+use crate::graphql::subscription::{NewMessage, NewMessageSender};
+
+pub mod schema;
+
+/// Shared GraphQL execution context, holding the single sqlite connection
+/// used to serve a request, and the broadcast channel the indexer publishes newly
+/// committed messages to for `Subscription` fields to follow.
+pub struct Context {
+    pub connection: Mutex<SqliteConnection>,
+    pub new_message_tx: NewMessageSender,
+}
+
+impl juniper::Context for Context {}
+
+impl Context {
+    /// The indexer calls this once per message, right after committing it to `threads`, so
+    /// any live `new_threads`/`new_posts_in_thread` subscribers see it without polling.
+    /// There being no active subscribers is not an error — `send` only fails when the
+    /// channel has zero receivers, which is the common case between subscriptions.
+    pub fn publish_new_message(&self, message: NewMessage) {
+        let _ = self.new_message_tx.send(message);
+    }
+}
@@ -0,0 +1,54 @@
+table! {
+    keys (id) {
+        id -> Integer,
+        key -> Text,
+    }
+}
+
+table! {
+    messages (key_id) {
+        key_id -> Integer,
+        author_id -> Nullable<Integer>,
+        content_type -> Nullable<Text>,
+    }
+}
+
+table! {
+    authors (id) {
+        id -> Nullable<Integer>,
+        author -> Text,
+        description -> Nullable<Text>,
+    }
+}
+
+table! {
+    contacts (id) {
+        id -> Integer,
+        author_id -> Nullable<Integer>,
+        contact_author_id -> Integer,
+        state -> Integer,
+        flume_seq -> Nullable<BigInt>,
+    }
+}
+
+table! {
+    threads (key_id) {
+        key_id -> Integer,
+        root_key_id -> Nullable<Integer>,
+        author_id -> Nullable<Integer>,
+        reply_author_id -> Nullable<Integer>,
+        content_type -> Text,
+        flume_seq -> Nullable<BigInt>,
+        is_decrypted -> Bool,
+        received_time -> BigInt,
+        asserted_time -> Nullable<BigInt>,
+    }
+}
+
+table! {
+    post_search_index (key_id, term) {
+        key_id -> Integer,
+        term -> Text,
+        term_frequency -> Integer,
+    }
+}
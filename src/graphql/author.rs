@@ -0,0 +1,227 @@
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+use crate::db::schema::authors::dsl::{author as authors_author, authors as authors_table, id as authors_id};
+use crate::db::schema::contacts::dsl::{
+    author_id as contacts_author_id, contact_author_id as contacts_contact_author_id,
+    contacts as contacts_table, flume_seq as contacts_flume_seq, state as contacts_state,
+};
+use crate::db::Context;
+
+use super::author_connection::*;
+use super::page_info::PageInfo;
+use super::root::{clamp_page_size, decode_cursor, encode_cursor};
+
+/// An SSB feed/author, identified by their internal `id` in the `authors` table.
+pub struct Author {
+    pub id: i32,
+}
+
+const CONTACT_STATE_FOLLOWING: i32 = 1;
+
+graphql_object!(Author: Context |&self| {
+    /// The public key string of this author's feed.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.lock()?;
+
+        let author = authors_table
+            .select(authors_author)
+            .filter(authors_id.eq(self.id))
+            .first::<String>(&(*connection))?;
+
+        Ok(author)
+    }
+
+    /// Authors that this author follows.
+    field following(
+        &executor,
+        /// Fetch the page of following before this cursor (keyset pagination, backward).
+        before: Option<String>,
+        /// Fetch the page of following after this cursor (keyset pagination, forward).
+        after: Option<String>,
+        /// Page size when paging forward with `after` (or for the first page). Defaults to 10.
+        first: Option<i32>,
+        /// Page size when paging backward with `before`. Defaults to 10.
+        last: Option<i32>,
+    ) -> FieldResult<AuthorConnection> {
+        let connection = executor.context().connection.lock()?;
+
+        let (paging_backward, limit) = paging_params(&before, first, last);
+
+        let mut query = contacts_table
+            .select((contacts_contact_author_id, contacts_flume_seq))
+            .filter(contacts_author_id.eq(self.id))
+            .filter(contacts_state.eq(CONTACT_STATE_FOLLOWING))
+            .into_boxed();
+
+        query = match (&before, &after) {
+            (Some(_), Some(_)) => {
+                Err("`before` and `after` can't be set at the same time.")?
+            },
+            (Some(b), None) => {
+                query.filter(contacts_flume_seq.gt(decode_cursor(b)?)).order(contacts_flume_seq.asc())
+            },
+            (None, Some(a)) => {
+                query.filter(contacts_flume_seq.lt(decode_cursor(a)?)).order(contacts_flume_seq.desc())
+            },
+            (None, None) => {
+                query.order(contacts_flume_seq.desc())
+            },
+        };
+
+        let rows = query
+            .limit((limit + 1) as i64)
+            .distinct()
+            .load::<(i32, Option<i64>)>(&(*connection))?;
+
+        Ok(rows_to_connection(rows, limit, paging_backward, before.is_some(), after.is_some()))
+    }
+
+    /// Authors that follow this author.
+    field followers(
+        &executor,
+        /// Fetch the page of followers before this cursor (keyset pagination, backward).
+        before: Option<String>,
+        /// Fetch the page of followers after this cursor (keyset pagination, forward).
+        after: Option<String>,
+        /// Page size when paging forward with `after` (or for the first page). Defaults to 10.
+        first: Option<i32>,
+        /// Page size when paging backward with `before`. Defaults to 10.
+        last: Option<i32>,
+    ) -> FieldResult<AuthorConnection> {
+        let connection = executor.context().connection.lock()?;
+
+        let (paging_backward, limit) = paging_params(&before, first, last);
+
+        let mut query = contacts_table
+            .select((contacts_author_id, contacts_flume_seq))
+            .filter(contacts_contact_author_id.eq(self.id))
+            .filter(contacts_state.eq(CONTACT_STATE_FOLLOWING))
+            .into_boxed();
+
+        query = match (&before, &after) {
+            (Some(_), Some(_)) => {
+                Err("`before` and `after` can't be set at the same time.")?
+            },
+            (Some(b), None) => {
+                query.filter(contacts_flume_seq.gt(decode_cursor(b)?)).order(contacts_flume_seq.asc())
+            },
+            (None, Some(a)) => {
+                query.filter(contacts_flume_seq.lt(decode_cursor(a)?)).order(contacts_flume_seq.desc())
+            },
+            (None, None) => {
+                query.order(contacts_flume_seq.desc())
+            },
+        };
+
+        let rows = query
+            .limit((limit + 1) as i64)
+            .distinct()
+            .load::<(Option<i32>, Option<i64>)>(&(*connection))?;
+
+        let rows = rows
+            .into_iter()
+            .filter_map(|(author_id, seq)| author_id.map(|author_id| (author_id, seq)))
+            .collect::<Vec<_>>();
+
+        Ok(rows_to_connection(rows, limit, paging_backward, before.is_some(), after.is_some()))
+    }
+});
+
+/// Resolve the paging direction and page size the same way `Query::threads` does: a `before`
+/// cursor means we're paging backward (using `last`, defaulting to 10), anything else pages
+/// forward (using `first`, defaulting to 10).
+fn paging_params(before: &Option<String>, first: Option<i32>, last: Option<i32>) -> (bool, i32) {
+    let paging_backward = before.is_some();
+
+    let limit = if paging_backward {
+        clamp_page_size(last, 10)
+    } else {
+        clamp_page_size(first, 10)
+    };
+
+    (paging_backward, limit)
+}
+
+/// Turn an (over-fetched by one) row set into an `AuthorConnection`, mirroring the keyset
+/// pagination bookkeeping in `Query::threads`.
+fn rows_to_connection(
+    mut rows: Vec<(i32, Option<i64>)>,
+    limit: i32,
+    paging_backward: bool,
+    before_given: bool,
+    after_given: bool,
+) -> AuthorConnection {
+    let has_extra_row = rows.len() > limit as usize;
+    rows.truncate(limit as usize);
+
+    if paging_backward {
+        rows.reverse();
+    }
+
+    let author_ids = rows.iter().map(|(id, _)| *id).collect::<Vec<i32>>();
+
+    let first_seq = rows.first().and_then(|(_, seq)| *seq);
+    let last_seq = rows.last().and_then(|(_, seq)| *seq);
+
+    let (has_next_page, has_previous_page) = if paging_backward {
+        (before_given, has_extra_row)
+    } else {
+        (has_extra_row, after_given)
+    };
+
+    let page_info = PageInfo {
+        start_cursor: first_seq.map(encode_cursor),
+        end_cursor: last_seq.map(encode_cursor).unwrap_or_default(),
+        has_next_page,
+        has_previous_page,
+    };
+
+    AuthorConnection { next: limit, author_ids, page_info }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_result_set_has_no_next_or_previous_page() {
+        let connection = rows_to_connection(vec![], 2, false, false, false);
+
+        assert!(connection.author_ids.is_empty());
+        assert_eq!(connection.page_info.has_next_page, false);
+        assert_eq!(connection.page_info.has_previous_page, false);
+    }
+
+    #[test]
+    fn exactly_limit_rows_has_no_next_page() {
+        let rows = vec![(1, Some(1)), (2, Some(2))];
+        let connection = rows_to_connection(rows, 2, false, false, false);
+
+        assert_eq!(connection.author_ids, vec![1, 2]);
+        assert_eq!(connection.page_info.has_next_page, false);
+    }
+
+    #[test]
+    fn limit_plus_one_rows_has_a_next_page_and_drops_the_extra_row() {
+        let rows = vec![(1, Some(1)), (2, Some(2)), (3, Some(3))];
+        let connection = rows_to_connection(rows, 2, false, false, false);
+
+        assert_eq!(connection.author_ids, vec![1, 2]);
+        assert_eq!(connection.page_info.has_next_page, true);
+    }
+
+    #[test]
+    fn paging_backward_reverses_rows_and_flips_which_side_has_the_extra_page() {
+        // Rows arrive in ascending keyset order (the `before`/backward query direction), with
+        // one extra row fetched to detect a further page; the result should come back in the
+        // usual descending display order, with the extra row setting `has_previous_page` (not
+        // `has_next_page`), and `has_next_page` set because a `before` cursor was given.
+        let rows = vec![(1, Some(1)), (2, Some(2)), (3, Some(3))];
+        let connection = rows_to_connection(rows, 2, true, true, false);
+
+        assert_eq!(connection.author_ids, vec![2, 1]);
+        assert_eq!(connection.page_info.has_next_page, true);
+        assert_eq!(connection.page_info.has_previous_page, true);
+    }
+}
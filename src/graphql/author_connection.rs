@@ -0,0 +1,28 @@
+use crate::db::Context;
+use juniper::FieldResult;
+
+use super::author::*;
+use super::page_info::PageInfo;
+
+/// A page of authors (e.g. a `followers` or `following` connection), using the same
+/// base64 `flume_seq` cursor scheme as `ThreadConnection`/`PostConnection`.
+pub struct AuthorConnection {
+    pub next: i32,
+    pub author_ids: Vec<i32>,
+    pub page_info: PageInfo,
+}
+
+graphql_object!(AuthorConnection: Context |&self| {
+    field authors(&executor) -> FieldResult<Vec<Author>> {
+        let authors = self.author_ids
+            .iter()
+            .map(|id| Author{id: *id})
+            .collect();
+
+        Ok(authors)
+    }
+
+    field page_info() -> &PageInfo {
+        &self.page_info
+    }
+});
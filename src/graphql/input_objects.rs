@@ -0,0 +1,21 @@
+/// Find public, private or all threads/posts.
+#[derive(GraphQLEnum, Clone, Copy, PartialEq)]
+pub enum Privacy {
+    Public,
+    Private,
+    All,
+}
+
+/// The axis that a connection of threads or posts is ordered by.
+#[derive(GraphQLEnum, Clone, Copy, PartialEq)]
+pub enum OrderBy {
+    /// Order by the time the message was received by the local log.
+    Received,
+    /// Order by the (untrusted, author-asserted) time embedded in the message itself.
+    Asserted,
+    /// Order by the causal ordering of the local log (`flume_seq`).
+    CausalOrdering,
+    /// Order by full-text search relevance score. Only valid when a `query` was provided;
+    /// falls back to `Received` ordering otherwise.
+    Relevance,
+}
@@ -0,0 +1,27 @@
+use crate::db::Context;
+
+/// Relay-style pagination info for a connection.
+pub struct PageInfo {
+    pub start_cursor: Option<String>,
+    pub end_cursor: String,
+    pub has_next_page: bool,
+    pub has_previous_page: bool,
+}
+
+graphql_object!(PageInfo: Context |&self| {
+    field start_cursor() -> &Option<String> {
+        &self.start_cursor
+    }
+
+    field end_cursor() -> &String {
+        &self.end_cursor
+    }
+
+    field has_next_page() -> bool {
+        self.has_next_page
+    }
+
+    field has_previous_page() -> bool {
+        self.has_previous_page
+    }
+});
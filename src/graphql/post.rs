@@ -0,0 +1,26 @@
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+use crate::db::schema::keys::dsl::{id as keys_id_col, key as keys_key_col, keys as keys_table};
+use crate::db::schema::messages::dsl::{key_id as messages_key_id, messages as messages_table};
+use crate::db::Context;
+
+/// A single SSB message resolved as a post (identified by its internal `key_id`).
+pub struct Post {
+    pub key_id: i32,
+}
+
+graphql_object!(Post: Context |&self| {
+    /// The public key string of this post's message.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.lock()?;
+
+        let key = keys_table
+            .inner_join(messages_table.on(messages_key_id.nullable().eq(keys_id_col)))
+            .select(keys_key_col)
+            .filter(messages_key_id.eq(self.key_id))
+            .first::<String>(&(*connection))?;
+
+        Ok(key)
+    }
+});
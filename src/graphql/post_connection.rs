@@ -0,0 +1,27 @@
+use crate::db::Context;
+use juniper::FieldResult;
+
+use super::page_info::PageInfo;
+use super::post::*;
+
+/// A page of posts, along with the `PageInfo` needed to fetch the next/previous page.
+pub struct PostConnection {
+    pub next: i32,
+    pub post_keys: Vec<i32>,
+    pub page_info: PageInfo,
+}
+
+graphql_object!(PostConnection: Context |&self| {
+    field posts(&executor) -> FieldResult<Vec<Post>> {
+        let posts = self.post_keys
+            .iter()
+            .map(|key_id| Post{key_id: *key_id})
+            .collect();
+
+        Ok(posts)
+    }
+
+    field page_info() -> &PageInfo {
+        &self.page_info
+    }
+});
@@ -1,6 +1,9 @@
 use super::page_info::PageInfo;
 use bytes::{ByteOrder, LittleEndian};
+use diesel::expression::BoxableExpression;
 use diesel::prelude::*;
+use diesel::sql_types::Bool;
+use diesel::sqlite::Sqlite;
 use juniper::FieldResult;
 
 use super::author::*;
@@ -15,22 +18,33 @@ use crate::db::schema::contacts::dsl::{
 };
 
 use crate::db::schema::authors::dsl::{
-    author as authors_author, authors as authors_table, id as authors_id,
+    author as authors_author, authors as authors_table, description as authors_description,
+    id as authors_id,
 };
 use crate::db::schema::keys::dsl::{id as keys_id_col, key as keys_key_col, keys as keys_table};
 use crate::db::schema::messages::dsl::{key_id as messages_key_id, messages as messages_table};
 use crate::db::Context;
 
 use crate::db::schema::threads::dsl::{
-    author_id as threads_author_id, content_type as threads_content_type,
-    flume_seq as threads_flume_seq, key_id as threads_key_id,
+    asserted_time as threads_asserted_time, author_id as threads_author_id,
+    content_type as threads_content_type, flume_seq as threads_flume_seq,
+    key_id as threads_key_id, received_time as threads_received_time,
     reply_author_id, root_key_id as threads_root_key_id, threads as threads_table,
     is_decrypted as threads_is_decrypted,
 };
+use crate::search;
 
 pub struct Query;
 
-fn decode_cursor(encoded: &str) -> Result<i64, String> {
+/// `contacts.state` value meaning the author has blocked the contact. See `author.rs`'s
+/// `CONTACT_STATE_FOLLOWING` for the corresponding "following" value.
+const CONTACT_STATE_BLOCKING: i32 = 2;
+
+/// A boxed, type-erased predicate over `threads_table`, used to assemble `Query::threads`'s
+/// selectors into either an AND'd chain (`match_all: true`) or a single grouped OR (the default).
+type BoolExpr = Box<dyn BoxableExpression<threads_table, Sqlite, SqlType = Bool>>;
+
+pub(crate) fn decode_cursor(encoded: &str) -> Result<i64, String> {
     match base64::decode(encoded) {
         Ok(ref bytes) if bytes.len() < 8 => {
             Err("Error decoding cursor. Is it a valid base64 encoded i64?".to_string())
@@ -40,13 +54,27 @@ fn decode_cursor(encoded: &str) -> Result<i64, String> {
     }
 }
 
-fn encode_cursor(cursor: i64) -> String {
+pub(crate) fn encode_cursor(cursor: i64) -> String {
     base64::encode(&(cursor as u64).to_le_bytes())
 }
 
-graphql_object!(Query: Context |&self| {
+/// Upper bound on any single page size. Caps how much a client-supplied `first`/`last`/`next`
+/// can over-fetch, and keeps the `limit + 1` trick used to compute `has_next_page` from
+/// overflowing.
+const MAX_PAGE_SIZE: i32 = 1000;
+
+/// Resolve a client-supplied page size (`first`/`last`/`next`) into a safe `LIMIT` value.
+/// A missing or negative value falls back to `default` — negative values are rejected rather
+/// than passed through, since SQLite treats a negative `LIMIT` as "no limit", which would defeat
+/// both the over-fetch-by-one page-info trick and any `rows.truncate(limit as usize)` call.
+pub(crate) fn clamp_page_size(requested: Option<i32>, default: i32) -> i32 {
+    match requested {
+        Some(value) if value >= 0 => value.min(MAX_PAGE_SIZE),
+        _ => default,
+    }
+}
 
-    //TODO Filtering by date ranges!
+graphql_object!(Query: Context |&self| {
 
     /// Find a thread by the key string of the root message.
     field thread(&executor, root_id: String, order_by = (OrderBy::Received): OrderBy) -> FieldResult<Thread> {
@@ -73,9 +101,14 @@ graphql_object!(Query: Context |&self| {
     /// where _either_ is true. The selectors are logically OR'd, **not** AND'd.
     field threads(
         &executor,
+        /// Fetch the page of threads before this cursor (keyset pagination, backward).
         before: Option<String>,
+        /// Fetch the page of threads after this cursor (keyset pagination, forward).
         after: Option<String>,
-        next = 10: i32,
+        /// Page size when paging forward with `after` (or for the first page). Defaults to 10.
+        first: Option<i32>,
+        /// Page size when paging backward with `before`. Defaults to 10.
+        last: Option<i32>,
         /// Find public, private or all threads.
         privacy = (Privacy::Public): Privacy,
         /// Include threads whose root message is authored by one of the provided authors
@@ -90,8 +123,21 @@ graphql_object!(Query: Context |&self| {
         mentions_authors: Option<Vec<String>>,
         /// Include threads that mention the provided channels.
         mentions_channels: Option<Vec<String>>,
+        /// When true, a thread must satisfy *every* provided selector (AND). When false
+        /// (the default), a thread matching *any* provided selector is included (OR).
+        match_all = false: bool,
         /// Order threads by asserted time, received time or causal ordering.
         order_by = (OrderBy::Received): OrderBy,
+        /// Only include threads received at or after this unix-millis timestamp.
+        received_after: Option<i64>,
+        /// Only include threads received at or before this unix-millis timestamp.
+        received_before: Option<i64>,
+        /// Only include threads asserted (by their author) at or after this unix-millis timestamp.
+        /// Since asserted time is attacker-controlled, pagination still orders by received time.
+        asserted_after: Option<i64>,
+        /// Only include threads asserted (by their author) at or before this unix-millis timestamp.
+        /// Since asserted time is attacker-controlled, pagination still orders by received time.
+        asserted_before: Option<i64>,
         ) -> FieldResult<ThreadConnection> {
         // Get the context from the executor.
         let connection = executor.context().connection.lock()?;
@@ -100,14 +146,24 @@ graphql_object!(Query: Context |&self| {
             .select((threads_key_id, threads_flume_seq))
             .into_boxed();
 
+        // Each selector below contributes a boolean predicate. In OR mode (the default) they're
+        // collected and combined into a single grouped `(sel1 OR sel2 OR ...)` expression so the
+        // group can't leak into the AND'd privacy/date-range filters applied afterwards; in
+        // `match_all` mode each predicate is AND'd onto `query` directly instead.
+        let mut or_predicates: Vec<BoolExpr> = Vec::new();
+
         if let Some(authors) = roots_authored_by {
             let author_key_ids = authors_table
                 .select(authors_id)
                 .filter(authors_author.eq_any(authors))
                 .load::<Option<i32>>(&(*connection))?;
 
-                query = query
-                    .or_filter(threads_author_id.nullable().eq_any(author_key_ids));
+            let predicate: BoolExpr = Box::new(threads_author_id.nullable().eq_any(author_key_ids));
+            if match_all {
+                query = query.filter(predicate);
+            } else {
+                or_predicates.push(predicate);
+            }
         }
 
         if let Some(authors) = roots_authored_by_someone_followed_by {
@@ -120,8 +176,12 @@ graphql_object!(Query: Context |&self| {
                 .filter(contacts_state.eq(1))
                 .load::<i32>(&(*connection))?;
 
-                query = query
-                    .or_filter(threads_author_id.nullable().eq_any(author_key_ids));
+            let predicate: BoolExpr = Box::new(threads_author_id.nullable().eq_any(author_key_ids));
+            if match_all {
+                query = query.filter(predicate);
+            } else {
+                or_predicates.push(predicate);
+            }
         }
 
         if let Some(authors) = has_replies_authored_by_someone_followed_by {
@@ -134,8 +194,12 @@ graphql_object!(Query: Context |&self| {
                 .filter(contacts_state.eq(1))
                 .load::<i32>(&(*connection))?;
 
-                query = query
-                    .or_filter(reply_author_id.nullable().eq_any(author_key_ids));
+            let predicate: BoolExpr = Box::new(reply_author_id.nullable().eq_any(author_key_ids));
+            if match_all {
+                query = query.filter(predicate);
+            } else {
+                or_predicates.push(predicate);
+            }
         }
 
         if let Some(authors) = has_replies_authored_by {
@@ -144,8 +208,18 @@ graphql_object!(Query: Context |&self| {
                 .filter(authors_author.eq_any(authors))
                 .load::<Option<i32>>(&(*connection))?;
 
-                query = query
-                    .or_filter(reply_author_id.nullable().eq_any(author_key_ids));
+            let predicate: BoolExpr = Box::new(reply_author_id.nullable().eq_any(author_key_ids));
+            if match_all {
+                query = query.filter(predicate);
+            } else {
+                or_predicates.push(predicate);
+            }
+        }
+
+        if !match_all {
+            if let Some(grouped) = or_predicates.into_iter().reduce(|acc, predicate| Box::new(acc.or(predicate))) {
+                query = query.filter(grouped);
+            }
         }
 
         query = match privacy {
@@ -160,64 +234,99 @@ graphql_object!(Query: Context |&self| {
             },
         };
 
+        if let Some(received_after) = received_after {
+            query = query.filter(threads_received_time.ge(received_after));
+        }
+
+        if let Some(received_before) = received_before {
+            query = query.filter(threads_received_time.le(received_before));
+        }
+
+        if let Some(asserted_after) = asserted_after {
+            query = query.filter(threads_asserted_time.ge(asserted_after));
+        }
+
+        if let Some(asserted_before) = asserted_before {
+            query = query.filter(threads_asserted_time.le(asserted_before));
+        }
+
+        // Backward pagination (`before`) walks the keyset the opposite way: ascending order
+        // filtered strictly greater-than the cursor, so the nearest-previous rows come first
+        // and get sliced off by `limit`, then reversed back into the usual DESC display order.
+        //
+        // Note: pagination always orders on `flume_seq`/received time, even when
+        // `order_by`/`asserted_*` reference asserted time — asserted timestamps are
+        // author-supplied and so can't be trusted to be monotonic with arrival order.
+        let paging_backward = before.is_some();
+
+        let limit = if paging_backward {
+            clamp_page_size(last, 10)
+        } else {
+            clamp_page_size(first, 10)
+        };
+
+        query = query
+            .filter(threads_root_key_id.is_null())
+            .filter(threads_content_type.eq("post"));
+
         query = match (&before, &after) {
+            (Some(_), Some(_)) => {
+                Err("`before` and `after` can't be set at the same time.")?
+            },
             (Some(b), None) => {
-                let start_cursor = decode_cursor(&b)?;
-            
+                let cursor = decode_cursor(&b)?;
                 query
-                    .filter(threads_flume_seq.gt(start_cursor))
+                    .filter(threads_flume_seq.gt(cursor))
+                    .order(threads_flume_seq.asc())
             },
             (None, Some(a)) => {
-                let start_cursor = decode_cursor(&a)?;
+                let cursor = decode_cursor(&a)?;
                 query
-                    .filter(threads_flume_seq.lt(start_cursor))
+                    .filter(threads_flume_seq.lt(cursor))
+                    .order(threads_flume_seq.desc())
             },
             (None, None) => {
-                query
+                query.order(threads_flume_seq.desc())
             },
-            (Some(_), Some(_)) => {
-                Err("Before and After can't be set at the same time.")?
-            
-            }
         };
 
-        let results = query
-            .filter(threads_root_key_id.is_null())
-            .filter(threads_content_type.eq("post"))
-            .order(threads_flume_seq.desc())
-            .limit(next as i64)
+        let mut rows = query
+            .limit((limit + 1) as i64)
             .distinct()
-            .load::<(i32, Option<i64>)>(&(*connection))
-            .unwrap();
+            .load::<(i32, Option<i64>)>(&(*connection))?;
 
-        let thread_keys = results
+        let has_extra_row = rows.len() > limit as usize;
+        rows.truncate(limit as usize);
+
+        if paging_backward {
+            rows.reverse();
+        }
+
+        let thread_keys = rows
             .iter()
             .map(|(key_id, _)| *key_id)
             .collect::<Vec<i32>>();
 
-        let first_seq: i64 = results
-            .first()
-            .map(|(_, seq)| *seq)
-            .ok_or("No results found")?
-            .ok_or("No results found")?;
-
-        let last_seq: i64 = results
-            .iter()
-            .last()
-            .map(|(_, seq)| *seq)
-            .ok_or("No results found")?
-            .ok_or("No results found")?;
+        let first_seq = rows.first().and_then(|(_, seq)| *seq);
+        let last_seq = rows.last().and_then(|(_, seq)| *seq);
 
-        let has_next_page = last_seq != 0; //TODO this hard to tell if there is a next page.
+        // If the client handed us a cursor there is, by construction, a page on the other
+        // side of it; the extra row (if any) tells us whether there's a page beyond that.
+        let (has_next_page, has_previous_page) = if paging_backward {
+            (before.is_some(), has_extra_row)
+        } else {
+            (has_extra_row, after.is_some())
+        };
 
         let page_info = PageInfo {
-            start_cursor: Some(encode_cursor(first_seq)),
-            end_cursor: encode_cursor(last_seq),
+            start_cursor: first_seq.map(encode_cursor),
+            end_cursor: last_seq.map(encode_cursor).unwrap_or_default(),
             has_next_page,
+            has_previous_page,
         };
 
         Ok(ThreadConnection {
-            next,
+            next: limit,
             thread_keys,
             page_info,
         })
@@ -250,27 +359,226 @@ graphql_object!(Query: Context |&self| {
         privacy = (Privacy::Public): Privacy,
         /// Find posts that are authored by the provided authors.
         authored_by: Option<String>,
-        /// Find posts that are referenced by the provided authors.
-        referenced_by_authors: Option<String>,
-        /// Find posts that mention the provided authors.
-        mentions_authors: Option<Vec<String>>,
-        /// Find posts that mention the provided channels.
-        mentions_channels: Option<Vec<String>>,
-        /// Order posts by asserted time, received time. Causal ordering not supported.
+        /// Order posts by relevance (requires `query`). Every other value, `Asserted` included,
+        /// orders posts by `flume_seq`, newest first.
         order_by = (OrderBy::Received): OrderBy,
+        next = 10: i32,
+        /// Only include posts received at or after this unix-millis timestamp.
+        received_after: Option<i64>,
+        /// Only include posts received at or before this unix-millis timestamp.
+        received_before: Option<i64>,
+        /// Only include posts asserted (by their author) at or after this unix-millis timestamp.
+        /// Since asserted time is attacker-controlled, pagination still orders by received time.
+        asserted_after: Option<i64>,
+        /// Only include posts asserted (by their author) at or before this unix-millis timestamp.
+        /// Since asserted time is attacker-controlled, pagination still orders by received time.
+        asserted_before: Option<i64>,
     ) -> FieldResult<PostConnection> {
+        let connection = executor.context().connection.lock()?;
 
-        Err("Not implemented")?
+        // Reject a negative or unreasonably large `next` before it reaches `LIMIT` — see
+        // `clamp_page_size`'s doc comment for why.
+        let next = clamp_page_size(Some(next), 10);
+
+        // The full-text index gives us a relevance-ranked candidate set of key_ids; every
+        // other filter below is then applied as an `AND` against that candidate set, the same
+        // way `threads` intersects its selectors against `threads_table`.
+        let ranked_hits = match &query {
+            Some(q) => Some(search::search_posts(&connection, q)?),
+            None => None,
+        };
+
+        if let (Some(hits), OrderBy::Relevance) = (&ranked_hits, order_by) {
+            // Relevance ordering is served directly from the ranked hits; the other selectors
+            // still narrow the set via `threads_table`, same as `Received`/`Asserted` ordering.
+            let candidate_key_ids: Vec<i32> = hits.iter().map(|hit| hit.key_id).collect();
+
+            let mut thread_query = threads_table
+                .select(threads_key_id)
+                .filter(threads_key_id.eq_any(&candidate_key_ids))
+                .into_boxed();
+
+            thread_query = match privacy {
+                Privacy::Private => thread_query.filter(threads_is_decrypted.eq(true)),
+                Privacy::Public => thread_query.filter(threads_is_decrypted.eq(false)),
+                Privacy::All => thread_query,
+            };
+
+            if let Some(authors) = &authored_by {
+                let author_key_ids = authors_table
+                    .select(authors_id)
+                    .filter(authors_author.eq(authors))
+                    .load::<Option<i32>>(&(*connection))?;
+
+                thread_query = thread_query.filter(threads_author_id.nullable().eq_any(author_key_ids));
+            }
+
+            if let Some(received_after) = received_after {
+                thread_query = thread_query.filter(threads_received_time.ge(received_after));
+            }
+
+            if let Some(received_before) = received_before {
+                thread_query = thread_query.filter(threads_received_time.le(received_before));
+            }
+
+            if let Some(asserted_after) = asserted_after {
+                thread_query = thread_query.filter(threads_asserted_time.ge(asserted_after));
+            }
+
+            if let Some(asserted_before) = asserted_before {
+                thread_query = thread_query.filter(threads_asserted_time.le(asserted_before));
+            }
+
+            let matching_key_ids = thread_query.load::<i32>(&(*connection))?;
+
+            // Re-order the matched key_ids by their already-computed relevance score, then cap
+            // to the requested page size (relevance ranking doesn't paginate with cursors) —
+            // keeping one extra match around first tells us whether there's a next page.
+            let mut ranked_matches: Vec<i32> = candidate_key_ids
+                .into_iter()
+                .filter(|key_id| matching_key_ids.contains(key_id))
+                .collect();
+
+            let has_next_page = ranked_matches.len() > next as usize;
+            ranked_matches.truncate(next as usize);
+            let post_keys = ranked_matches;
+
+            let page_info = PageInfo {
+                start_cursor: None,
+                end_cursor: String::new(),
+                has_next_page,
+                has_previous_page: false,
+            };
+
+            return Ok(PostConnection { next, post_keys, page_info });
+        }
+
+        let mut post_query = threads_table
+            .select((threads_key_id, threads_flume_seq))
+            .into_boxed();
+
+        if let Some(hits) = &ranked_hits {
+            let candidate_key_ids: Vec<i32> = hits.iter().map(|hit| hit.key_id).collect();
+            post_query = post_query.filter(threads_key_id.eq_any(candidate_key_ids));
+        }
+
+        post_query = match privacy {
+            Privacy::Private => post_query.filter(threads_is_decrypted.eq(true)),
+            Privacy::Public => post_query.filter(threads_is_decrypted.eq(false)),
+            Privacy::All => post_query,
+        };
+
+        if let Some(authors) = &authored_by {
+            let author_key_ids = authors_table
+                .select(authors_id)
+                .filter(authors_author.eq(authors))
+                .load::<Option<i32>>(&(*connection))?;
+
+            post_query = post_query.filter(threads_author_id.nullable().eq_any(author_key_ids));
+        }
+
+        if let Some(received_after) = received_after {
+            post_query = post_query.filter(threads_received_time.ge(received_after));
+        }
+
+        if let Some(received_before) = received_before {
+            post_query = post_query.filter(threads_received_time.le(received_before));
+        }
+
+        if let Some(asserted_after) = asserted_after {
+            post_query = post_query.filter(threads_asserted_time.ge(asserted_after));
+        }
+
+        if let Some(asserted_before) = asserted_before {
+            post_query = post_query.filter(threads_asserted_time.le(asserted_before));
+        }
+
+        // Pagination here still orders on `flume_seq`/received time, even when `asserted_*`
+        // bounds are in play — asserted timestamps are author-supplied and untrusted, so they
+        // can't be relied on to be monotonic with arrival order.
+        //
+        // Over-fetch by one row (same trick `threads` uses) so we can tell whether there's a
+        // next page without a second query.
+        let mut results = post_query
+            .order(threads_flume_seq.desc())
+            .limit((next + 1) as i64)
+            .distinct()
+            .load::<(i32, Option<i64>)>(&(*connection))?;
+
+        let has_next_page = results.len() > next as usize;
+        results.truncate(next as usize);
+
+        let post_keys = results.iter().map(|(key_id, _)| *key_id).collect::<Vec<i32>>();
+
+        let first_seq = results.first().and_then(|(_, seq)| *seq);
+        let last_seq = results.last().and_then(|(_, seq)| *seq);
+
+        let page_info = PageInfo {
+            start_cursor: first_seq.map(encode_cursor),
+            end_cursor: last_seq.map(encode_cursor).unwrap_or_default(),
+            has_next_page,
+            has_previous_page: false,
+        };
+
+        Ok(PostConnection { next, post_keys, page_info })
     }
 
     /// Find an author by their public key string.
     field author(&executor, id: String) -> FieldResult<Author>{
-        Err("Not implemented")?
+        let connection = executor.context().connection.lock()?;
+
+        let id = authors_table
+            .select(authors_id)
+            .filter(authors_author.eq(id))
+            .first::<Option<i32>>(&(*connection))?
+            .ok_or("No author found with that id")?;
+
+        Ok(Author{id})
     }
 
     /// Search for an author by a query string. Will search names and optionally descriptions too.
     field authors(&executor, query: String, exclude_if_blocked_by: Option<Vec<String>>, include_descriptions = false: bool) -> FieldResult<Vec<Author>>{
-        Err("Not implemented")?
+        let connection = executor.context().connection.lock()?;
+
+        let like_pattern = format!("%{}%", query);
+
+        let mut author_query = authors_table
+            .select(authors_id)
+            .into_boxed();
+
+        author_query = if include_descriptions {
+            author_query
+                .filter(authors_author.like(like_pattern.clone()))
+                .or_filter(authors_description.like(like_pattern))
+        } else {
+            author_query.filter(authors_author.like(like_pattern))
+        };
+
+        // Blocking is a distinct `contacts.state` from following (`1`); exclude any author
+        // blocked by one of the feeds the caller passed in.
+        if let Some(blocked_by) = exclude_if_blocked_by {
+            let blocker_ids = authors_table
+                .select(authors_id)
+                .filter(authors_author.eq_any(blocked_by))
+                .load::<Option<i32>>(&(*connection))?;
+
+            let blocked_author_ids = contacts_table
+                .select(contacts_contact_author_id)
+                .filter(contacts_author_id.eq_any(blocker_ids))
+                .filter(contacts_state.eq(CONTACT_STATE_BLOCKING))
+                .load::<i32>(&(*connection))?;
+
+            author_query = author_query.filter(authors_id.ne_all(blocked_author_ids));
+        }
+
+        let author_ids = author_query.load::<Option<i32>>(&(*connection))?;
+
+        let authors = author_ids
+            .into_iter()
+            .filter_map(|id| id.map(|id| Author{id}))
+            .collect();
+
+        Ok(authors)
     }
 
     /// Find all the message types we know about
@@ -288,3 +596,97 @@ graphql_object!(Query: Context |&self| {
         Err("Not implemented")?
     }
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::sqlite::SqliteConnection;
+
+    #[test]
+    fn clamp_page_size_falls_back_to_default_when_absent_or_negative() {
+        assert_eq!(clamp_page_size(None, 10), 10);
+        assert_eq!(clamp_page_size(Some(-1), 10), 10);
+        assert_eq!(clamp_page_size(Some(-2_147_483_648), 10), 10);
+    }
+
+    #[test]
+    fn clamp_page_size_passes_through_valid_values() {
+        assert_eq!(clamp_page_size(Some(0), 10), 0);
+        assert_eq!(clamp_page_size(Some(5), 10), 5);
+    }
+
+    #[test]
+    fn clamp_page_size_caps_at_the_upper_bound() {
+        // Also guards against the `(limit + 1) as i64` over-fetch trick overflowing when a
+        // client passes something like `first: 2147483647`.
+        assert_eq!(clamp_page_size(Some(i32::MAX), 10), MAX_PAGE_SIZE);
+    }
+
+    fn test_connection() -> SqliteConnection {
+        let connection = SqliteConnection::establish(":memory:").unwrap();
+        diesel::sql_query(
+            "CREATE TABLE threads (
+                key_id INTEGER NOT NULL PRIMARY KEY,
+                root_key_id INTEGER,
+                author_id INTEGER,
+                reply_author_id INTEGER,
+                content_type TEXT NOT NULL,
+                flume_seq BIGINT,
+                is_decrypted BOOLEAN NOT NULL,
+                received_time BIGINT NOT NULL,
+                asserted_time BIGINT
+            )",
+        )
+        .execute(&connection)
+        .unwrap();
+
+        // key_id 1: author 1 only.    key_id 2: reply author 2 only.
+        // key_id 3: both.             key_id 4: neither.
+        diesel::sql_query(
+            "INSERT INTO threads (key_id, root_key_id, author_id, reply_author_id, content_type, flume_seq, is_decrypted, received_time)
+             VALUES (1, NULL, 1, NULL, 'post', 1, 0, 0),
+                    (2, NULL, NULL, 2, 'post', 2, 0, 0),
+                    (3, NULL, 1, 2, 'post', 3, 0, 0),
+                    (4, NULL, 9, 9, 'post', 4, 0, 0)",
+        )
+        .execute(&connection)
+        .unwrap();
+
+        connection
+    }
+
+    /// Mirrors `Query::threads`'s `match_all` assembly: each selector's predicate either gets
+    /// AND'd directly onto the query, or stashed to be grouped into one `(p1 OR p2)` later.
+    fn matching_key_ids(connection: &SqliteConnection, match_all: bool) -> Vec<i32> {
+        let mut query = threads_table.select(threads_key_id).into_boxed();
+
+        let by_author: BoolExpr = Box::new(threads_author_id.eq(Some(1)));
+        let by_reply_author: BoolExpr = Box::new(reply_author_id.eq(Some(2)));
+
+        if match_all {
+            query = query.filter(by_author).filter(by_reply_author);
+            query.load::<i32>(connection).unwrap()
+        } else {
+            let or_predicates: Vec<BoolExpr> = vec![by_author, by_reply_author];
+            if let Some(grouped) = or_predicates.into_iter().reduce(|acc, p| Box::new(acc.or(p))) {
+                query = query.filter(grouped);
+            }
+            query.load::<i32>(connection).unwrap()
+        }
+    }
+
+    #[test]
+    fn match_all_false_ors_selectors_together() {
+        let connection = test_connection();
+        let mut key_ids = matching_key_ids(&connection, false);
+        key_ids.sort();
+        assert_eq!(key_ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn match_all_true_ands_selectors_together() {
+        let connection = test_connection();
+        let key_ids = matching_key_ids(&connection, true);
+        assert_eq!(key_ids, vec![3]);
+    }
+}
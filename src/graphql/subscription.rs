@@ -0,0 +1,241 @@
+use std::pin::Pin;
+
+use diesel::prelude::*;
+use futures::{stream, Stream, StreamExt};
+use juniper::FieldError;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::db::schema::authors::dsl::{author as authors_author, authors as authors_table, id as authors_id};
+use crate::db::schema::contacts::dsl::{
+    author_id as contacts_author_id, contact_author_id as contacts_contact_author_id,
+    contacts as contacts_table, state as contacts_state,
+};
+use crate::db::schema::threads::dsl::{
+    author_id as threads_author_id, content_type as threads_content_type,
+    flume_seq as threads_flume_seq, is_decrypted as threads_is_decrypted,
+    key_id as threads_key_id, root_key_id as threads_root_key_id, threads as threads_table,
+};
+use crate::db::Context;
+
+use super::input_objects::Privacy;
+use super::post::*;
+use super::thread::*;
+
+/// Announced by the indexer every time a new message is committed to the local log, so
+/// subscriptions can pick out the ones they care about without re-querying the whole table.
+#[derive(Clone)]
+pub struct NewMessage {
+    pub flume_seq: i64,
+    pub key_id: i32,
+    pub root_key_id: Option<i32>,
+    pub author_id: Option<i32>,
+    pub is_decrypted: bool,
+    pub content_type: String,
+}
+
+pub type NewMessageSender = broadcast::Sender<NewMessage>;
+
+pub struct Subscription;
+
+type ThreadStream = Pin<Box<dyn Stream<Item = Result<Thread, FieldError>> + Send>>;
+type PostStream = Pin<Box<dyn Stream<Item = Result<Post, FieldError>> + Send>>;
+
+fn resolve_followed_by(
+    connection: &diesel::sqlite::SqliteConnection,
+    authors: Vec<String>,
+) -> QueryResult<Vec<i32>> {
+    authors_table
+        .inner_join(contacts_table.on(authors_id.eq(contacts_author_id.nullable())))
+        .select(contacts_contact_author_id)
+        .filter(authors_author.eq_any(authors))
+        .filter(contacts_state.eq(1))
+        .load::<i32>(connection)
+}
+
+#[juniper::graphql_subscription(Context = Context)]
+impl Subscription {
+    /// New root threads, filtered the same way as `Query::threads`'s `privacy` and
+    /// `roots_authored_by_someone_followed_by` selectors.
+    ///
+    /// If `after_flume_seq` is given, everything committed since that cursor is replayed
+    /// before the stream switches over to following new messages live.
+    async fn new_threads(
+        privacy: Privacy,
+        roots_authored_by_someone_followed_by: Option<Vec<String>>,
+        after_flume_seq: Option<i64>,
+        context: &Context,
+    ) -> ThreadStream {
+        let rx = context.new_message_tx.subscribe();
+
+        let catch_up: Vec<NewMessage> = match after_flume_seq {
+            Some(after) => {
+                let connection = match context.connection.lock() {
+                    Ok(connection) => connection,
+                    Err(err) => return Box::pin(stream::once(async move { Err(FieldError::from(err.to_string())) })),
+                };
+
+                let rows = threads_table
+                    .select((threads_key_id, threads_flume_seq, threads_root_key_id, threads_author_id, threads_is_decrypted, threads_content_type))
+                    .filter(threads_flume_seq.gt(after))
+                    .order(threads_flume_seq.asc())
+                    .load::<(i32, Option<i64>, Option<i32>, Option<i32>, bool, String)>(&(*connection));
+
+                match rows {
+                    Ok(rows) => rows
+                        .into_iter()
+                        .filter_map(|(key_id, flume_seq, root_key_id, author_id, is_decrypted, content_type)| {
+                            flume_seq.map(|flume_seq| NewMessage { flume_seq, key_id, root_key_id, author_id, is_decrypted, content_type })
+                        })
+                        .collect(),
+                    Err(err) => return Box::pin(stream::once(async move { Err(FieldError::from(err.to_string())) })),
+                }
+            }
+            None => Vec::new(),
+        };
+
+        let followed_by_key_ids = match roots_authored_by_someone_followed_by {
+            Some(authors) => {
+                let connection = match context.connection.lock() {
+                    Ok(connection) => connection,
+                    Err(err) => return Box::pin(stream::once(async move { Err(FieldError::from(err.to_string())) })),
+                };
+
+                match resolve_followed_by(&connection, authors) {
+                    Ok(ids) => Some(ids),
+                    Err(err) => return Box::pin(stream::once(async move { Err(FieldError::from(err.to_string())) })),
+                }
+            }
+            None => None,
+        };
+
+        let matches_selectors = move |message: &NewMessage| {
+            if message.root_key_id.is_some() {
+                return false;
+            }
+
+            // Match `Query::threads`'s `threads_content_type.eq("post")` filter, so a
+            // contact/vote/about-type message with a null `root_key_id` isn't pushed as a
+            // "new thread" when it would never show up via `threads`.
+            if message.content_type != "post" {
+                return false;
+            }
+
+            let matches_privacy = match privacy {
+                Privacy::Private => message.is_decrypted,
+                Privacy::Public => !message.is_decrypted,
+                Privacy::All => true,
+            };
+
+            let matches_author = match &followed_by_key_ids {
+                Some(ids) => message.author_id.map_or(false, |id| ids.contains(&id)),
+                None => true,
+            };
+
+            matches_privacy && matches_author
+        };
+
+        let live = BroadcastStream::new(rx).filter_map(|message| async move { message.ok() });
+
+        let combined = stream::iter(catch_up).chain(live).filter(move |message| {
+            let matches = matches_selectors(message);
+            async move { matches }
+        });
+
+        Box::pin(combined.map(|message| Ok(Thread { root: Post { key_id: message.key_id } })))
+    }
+
+    /// New posts committed as replies within the thread rooted at `root_id`.
+    async fn new_posts_in_thread(
+        root_id: String,
+        after_flume_seq: Option<i64>,
+        context: &Context,
+    ) -> PostStream {
+        let root_key_id = {
+            let connection = match context.connection.lock() {
+                Ok(connection) => connection,
+                Err(err) => return Box::pin(stream::once(async move { Err(FieldError::from(err.to_string())) })),
+            };
+
+            use crate::db::schema::keys::dsl::{id as keys_id_col, key as keys_key_col, keys as keys_table};
+            use crate::db::schema::messages::dsl::{key_id as messages_key_id, messages as messages_table};
+
+            let root_key_id = keys_table
+                .inner_join(messages_table.on(messages_key_id.nullable().eq(keys_id_col)))
+                .select(messages_key_id)
+                .filter(keys_key_col.eq(root_id))
+                .first::<i32>(&(*connection));
+
+            match root_key_id {
+                Ok(id) => id,
+                Err(err) => return Box::pin(stream::once(async move { Err(FieldError::from(err.to_string())) })),
+            }
+        };
+
+        let rx = context.new_message_tx.subscribe();
+
+        let catch_up: Vec<NewMessage> = match after_flume_seq {
+            Some(after) => {
+                let connection = match context.connection.lock() {
+                    Ok(connection) => connection,
+                    Err(err) => return Box::pin(stream::once(async move { Err(FieldError::from(err.to_string())) })),
+                };
+
+                let rows = threads_table
+                    .select((threads_key_id, threads_flume_seq, threads_root_key_id, threads_author_id, threads_is_decrypted, threads_content_type))
+                    .filter(threads_flume_seq.gt(after))
+                    .filter(threads_root_key_id.eq(root_key_id))
+                    .order(threads_flume_seq.asc())
+                    .load::<(i32, Option<i64>, Option<i32>, Option<i32>, bool, String)>(&(*connection));
+
+                match rows {
+                    Ok(rows) => rows
+                        .into_iter()
+                        .filter_map(|(key_id, flume_seq, root_key_id, author_id, is_decrypted, content_type)| {
+                            flume_seq.map(|flume_seq| NewMessage { flume_seq, key_id, root_key_id, author_id, is_decrypted, content_type })
+                        })
+                        .collect(),
+                    Err(err) => return Box::pin(stream::once(async move { Err(FieldError::from(err.to_string())) })),
+                }
+            }
+            None => Vec::new(),
+        };
+
+        let live = BroadcastStream::new(rx).filter_map(|message| async move { message.ok() });
+
+        let combined = stream::iter(catch_up)
+            .chain(live)
+            .filter(move |message| {
+                let matches = message.root_key_id == Some(root_key_id);
+                async move { matches }
+            });
+
+        Box::pin(combined.map(|message| Ok(Post { key_id: message.key_id })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_message(key_id: i32) -> NewMessage {
+        NewMessage {
+            flume_seq: 1,
+            key_id,
+            root_key_id: None,
+            author_id: None,
+            is_decrypted: false,
+            content_type: "post".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn a_published_message_reaches_a_live_subscriber() {
+        let (tx, mut rx) = broadcast::channel(16);
+
+        tx.send(sample_message(42)).unwrap();
+
+        let received = rx.recv().await.unwrap();
+        assert_eq!(received.key_id, 42);
+    }
+}
@@ -0,0 +1,15 @@
+use crate::db::Context;
+
+use super::post::*;
+
+/// A thread of posts, rooted at a single post.
+pub struct Thread {
+    pub root: Post,
+}
+
+graphql_object!(Thread: Context |&self| {
+    /// The root post of this thread.
+    field root() -> &Post {
+        &self.root
+    }
+});
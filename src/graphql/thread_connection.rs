@@ -0,0 +1,28 @@
+use crate::db::Context;
+use juniper::FieldResult;
+
+use super::page_info::PageInfo;
+use super::post::*;
+use super::thread::*;
+
+/// A page of threads, along with the `PageInfo` needed to fetch the next/previous page.
+pub struct ThreadConnection {
+    pub next: i32,
+    pub thread_keys: Vec<i32>,
+    pub page_info: PageInfo,
+}
+
+graphql_object!(ThreadConnection: Context |&self| {
+    field threads(&executor) -> FieldResult<Vec<Thread>> {
+        let threads = self.thread_keys
+            .iter()
+            .map(|key_id| Thread{root: Post{key_id: *key_id}})
+            .collect();
+
+        Ok(threads)
+    }
+
+    field page_info() -> &PageInfo {
+        &self.page_info
+    }
+});
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+use crate::db::schema::post_search_index;
+use crate::db::schema::post_search_index::dsl::{
+    key_id as index_key_id, post_search_index as index_table, term as index_term,
+    term_frequency as index_term_frequency,
+};
+
+/// A post matched by a full-text search, along with its relevance score.
+pub struct SearchHit {
+    pub key_id: i32,
+    pub score: f64,
+}
+
+#[derive(Insertable)]
+#[table_name = "post_search_index"]
+struct NewPosting {
+    key_id: i32,
+    term: String,
+    term_frequency: i32,
+}
+
+/// Tokenize `text` and (re-)write its posting list into `post_search_index`, so it becomes
+/// findable via `search_posts`. This should be called by the indexer every time a post message
+/// is committed to the log, keyed by that post's `key_id`; re-indexing the same `key_id` (e.g.
+/// after a backfill) replaces its previous postings rather than accumulating duplicates.
+pub fn index_post(connection: &SqliteConnection, key_id: i32, text: &str) -> QueryResult<()> {
+    let mut term_frequencies: HashMap<String, i32> = HashMap::new();
+    for term in tokenize(text) {
+        *term_frequencies.entry(term).or_insert(0) += 1;
+    }
+
+    connection.transaction(|| {
+        diesel::delete(index_table.filter(index_key_id.eq(key_id))).execute(connection)?;
+
+        let postings: Vec<NewPosting> = term_frequencies
+            .into_iter()
+            .map(|(term, term_frequency)| NewPosting { key_id, term, term_frequency })
+            .collect();
+
+        if !postings.is_empty() {
+            diesel::insert_into(index_table)
+                .values(&postings)
+                .execute(connection)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Split a search query into lowercased, alphanumeric tokens.
+///
+/// This is intentionally the same tokenization used when posts are indexed, so that
+/// query terms line up with the terms stored in `post_search_index`.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+/// Look up posts matching `query` against the inverted index, ranked by a simple TF score
+/// summed across all query terms (a term hit in more posting lists, or hit more often in one
+/// post, scores higher). Posts matching none of the terms are not returned.
+pub fn search_posts(connection: &SqliteConnection, query: &str) -> QueryResult<Vec<SearchHit>> {
+    let terms = tokenize(query);
+
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let postings = index_table
+        .select((index_key_id, index_term_frequency))
+        .filter(index_term.eq_any(terms))
+        .load::<(i32, i32)>(connection)?;
+
+    let mut scores: Vec<(i32, f64)> = Vec::new();
+    for (key_id, term_frequency) in postings {
+        match scores.iter_mut().find(|(id, _)| *id == key_id) {
+            Some((_, score)) => *score += f64::from(term_frequency),
+            None => scores.push((key_id, f64::from(term_frequency))),
+        }
+    }
+
+    scores.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+
+    Ok(scores
+        .into_iter()
+        .map(|(key_id, score)| SearchHit { key_id, score })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_connection() -> SqliteConnection {
+        let connection = SqliteConnection::establish(":memory:").unwrap();
+        diesel::sql_query(
+            "CREATE TABLE post_search_index (
+                key_id INTEGER NOT NULL,
+                term TEXT NOT NULL,
+                term_frequency INTEGER NOT NULL
+            )",
+        )
+        .execute(&connection)
+        .unwrap();
+        connection
+    }
+
+    #[test]
+    fn indexed_posts_are_retrievable_by_search() {
+        let connection = test_connection();
+
+        index_post(&connection, 1, "the quick brown fox").unwrap();
+        index_post(&connection, 2, "the lazy dog").unwrap();
+
+        let hits = search_posts(&connection, "fox").unwrap();
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key_id, 1);
+    }
+
+    #[test]
+    fn reindexing_a_post_replaces_its_old_postings() {
+        let connection = test_connection();
+
+        index_post(&connection, 1, "fox").unwrap();
+        index_post(&connection, 1, "dog").unwrap();
+
+        assert_eq!(search_posts(&connection, "fox").unwrap().len(), 0);
+        assert_eq!(search_posts(&connection, "dog").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn unmatched_queries_return_no_hits() {
+        let connection = test_connection();
+
+        index_post(&connection, 1, "the quick brown fox").unwrap();
+
+        assert!(search_posts(&connection, "giraffe").unwrap().is_empty());
+    }
+}